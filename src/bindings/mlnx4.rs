@@ -71,6 +71,11 @@ pub(super) unsafe fn verbs_get_ctx(ctx: *const ibv_context) -> *mut verbs_contex
 pub unsafe fn ibv_create_flow(qp: *mut ibv_qp, flow_attr: *mut ibv_flow_attr) -> *mut ibv_flow {
     let vctx = verbs_get_ctx_op!((*qp).context, create_flow);
     if vctx.is_null() {
+        // Same convention as `ibv_open_xrcd` below: a provider that doesn't
+        // support this extended op has no `int` return value to carry
+        // `-ENOSYS` through, so set `errno` ourselves rather than leaving
+        // the caller to read a stale value via `last_os_error()`.
+        *__errno_location() = ENOSYS;
         std::ptr::null_mut()
     } else {
         (*vctx).create_flow.unwrap()(qp, flow_attr)
@@ -0,0 +1,145 @@
+use std::os::unix::io::RawFd;
+use std::sync::mpsc;
+use std::thread;
+use std::{io, mem};
+
+use crate::bindings::*;
+use anyhow::Result;
+
+use super::context::Context;
+
+/// A single asynchronous event delivered by the device.
+///
+/// Acknowledges itself (`ibv_ack_async_event`) on drop, so callers can't
+/// forget to -- leaving events unacknowledged eventually blocks the kernel
+/// from freeing the object the event fired on.
+pub struct AsyncEvent {
+    raw: ibv_async_event,
+}
+
+// SAFETY: `ibv_async_event` only carries a `event_type` tag plus raw handles
+// (QP/CQ/SRQ pointers, a port number) read out via `kind()`; nothing about
+// it is tied to the thread that called `ibv_get_async_event`, and acking it
+// from another thread via `Drop` is explicitly supported by the verbs API.
+unsafe impl Send for AsyncEvent {}
+
+/// Safe classification of `ibv_async_event.event_type`, carrying the handle
+/// read out of the matching `ibv_async_event_element_union_t` member (`qp`,
+/// `cq`, `srq` or `port_num`) back to the object the event fired on.
+///
+/// QP/CQ/SRQ handles are raw pointers rather than owning `Qp`/`Cq`/`Srq`
+/// values: the event only proves the underlying object is still alive at
+/// the moment it fired, not for the lifetime of `AsyncEvent`.
+#[derive(Debug, Clone, Copy)]
+pub enum AsyncEventKind {
+    QpFatal(*mut ibv_qp),
+    QpAccessErr(*mut ibv_qp),
+    QpPathMig(*mut ibv_qp),
+    QpPathMigErr(*mut ibv_qp),
+    CqErr(*mut ibv_cq),
+    SrqErr(*mut ibv_srq),
+    SrqLimitReached(*mut ibv_srq),
+    PortActive(u8),
+    PortErr(u8),
+    DeviceFatal,
+    /// An event type this crate doesn't classify yet.
+    Other(ibv_event_type::Type),
+}
+
+impl AsyncEvent {
+    /// Classify this event and recover the handle to the object it fired on.
+    pub fn kind(&self) -> AsyncEventKind {
+        use ibv_event_type::*;
+        // SAFETY: which union member is live is determined by `event_type`,
+        // which we match on before reading it.
+        match self.raw.event_type {
+            IBV_EVENT_QP_FATAL => AsyncEventKind::QpFatal(unsafe { self.raw.element.qp }),
+            IBV_EVENT_QP_ACCESS_ERR => AsyncEventKind::QpAccessErr(unsafe { self.raw.element.qp }),
+            IBV_EVENT_PATH_MIG => AsyncEventKind::QpPathMig(unsafe { self.raw.element.qp }),
+            IBV_EVENT_PATH_MIG_ERR => {
+                AsyncEventKind::QpPathMigErr(unsafe { self.raw.element.qp })
+            }
+            IBV_EVENT_CQ_ERR => AsyncEventKind::CqErr(unsafe { self.raw.element.cq }),
+            IBV_EVENT_SRQ_ERR => AsyncEventKind::SrqErr(unsafe { self.raw.element.srq }),
+            IBV_EVENT_SRQ_LIMIT_REACHED => {
+                AsyncEventKind::SrqLimitReached(unsafe { self.raw.element.srq })
+            }
+            IBV_EVENT_PORT_ACTIVE => {
+                AsyncEventKind::PortActive(unsafe { self.raw.element.port_num } as u8)
+            }
+            IBV_EVENT_PORT_ERR => {
+                AsyncEventKind::PortErr(unsafe { self.raw.element.port_num } as u8)
+            }
+            IBV_EVENT_DEVICE_FATAL => AsyncEventKind::DeviceFatal,
+            other => AsyncEventKind::Other(other),
+        }
+    }
+}
+
+impl Drop for AsyncEvent {
+    fn drop(&mut self) {
+        // SAFETY: FFI; every `AsyncEvent` was built from a successful
+        // `ibv_get_async_event` call, so it always has a matching ack owed.
+        unsafe { ibv_ack_async_event(&mut self.raw) };
+    }
+}
+
+impl Context {
+    /// Block until a single asynchronous event is available and return it.
+    ///
+    /// The async-event file descriptor is blocking by default; use
+    /// `async_fd` instead if you want to poll it from an epoll/tokio
+    /// reactor.
+    pub fn poll_async_event(&self) -> Result<AsyncEvent> {
+        // SAFETY: will be filled by the FFI call.
+        let mut raw = unsafe { mem::zeroed::<ibv_async_event>() };
+        // SAFETY: FFI.
+        let ret = unsafe { ibv_get_async_event(self.as_raw(), &mut raw) };
+        if ret != 0 {
+            return Err(anyhow::anyhow!(io::Error::last_os_error()));
+        }
+        Ok(AsyncEvent { raw })
+    }
+
+    /// Get the file descriptor that becomes readable when an asynchronous
+    /// event is pending.
+    #[inline]
+    pub fn async_fd(&self) -> RawFd {
+        // SAFETY: the context is valid for as long as `self` is alive.
+        unsafe { (*self.as_raw()).async_fd }
+    }
+}
+
+/// Background thread that continuously calls `Context::poll_async_event` and
+/// forwards events through a channel, so applications don't need to dedicate
+/// their own thread to the blocking call.
+pub struct AsyncEventStream {
+    rx: mpsc::Receiver<AsyncEvent>,
+    _handle: thread::JoinHandle<()>,
+}
+
+impl AsyncEventStream {
+    /// Spawn a thread polling asynchronous events on `ctx`.
+    pub fn spawn(ctx: Context) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            while let Ok(event) = ctx.poll_async_event() {
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        AsyncEventStream {
+            rx,
+            _handle: handle,
+        }
+    }
+
+    /// Block until the next asynchronous event arrives.
+    pub fn recv(&self) -> Result<AsyncEvent> {
+        self.rx
+            .recv()
+            .map_err(|_| anyhow::anyhow!("async event stream closed"))
+    }
+}
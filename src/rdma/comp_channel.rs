@@ -0,0 +1,115 @@
+use std::os::unix::io::RawFd;
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::{fmt, io, ptr};
+
+use super::context::Context;
+use super::cq::Cq;
+use crate::bindings::*;
+use anyhow::Result;
+
+#[allow(dead_code)]
+struct CompChannelInner {
+    channel: NonNull<ibv_comp_channel>,
+
+    /// Keep the context alive for as long as the channel lives.
+    ctx: Context,
+}
+
+unsafe impl Send for CompChannelInner {}
+unsafe impl Sync for CompChannelInner {}
+
+impl fmt::Debug for CompChannelInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompChannel")
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+impl Drop for CompChannelInner {
+    fn drop(&mut self) {
+        // SAFETY: FFI.
+        unsafe { ibv_destroy_comp_channel(self.channel.as_ptr()) };
+    }
+}
+
+/// Completion channel.
+///
+/// By default a `Cq` must be polled, which burns a core even when no
+/// completions are pending. Binding a CQ to a `CompChannel` (see
+/// `Context::create_cq`) instead lets a thread block in `wait` until a
+/// completion actually arrives.
+///
+/// The correct usage loop, to avoid missing completions that land between
+/// the last poll and the wait:
+/// 1. Arm notification with `Cq::req_notify`.
+/// 2. Drain the CQ with the regular poll methods until it is empty.
+/// 3. Call `wait`, which blocks until the next completion.
+/// 4. Go back to step 1 -- a completion may have arrived while draining, so
+///    re-arming before the next `wait` would otherwise race.
+///
+/// This type is a simple wrapper of an `Arc` and is guaranteed to have the
+/// same memory layout with it.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct CompChannel {
+    inner: Arc<CompChannelInner>,
+}
+
+impl CompChannel {
+    /// Create a completion channel on the given context.
+    pub fn create(ctx: &Context) -> Result<Self> {
+        // SAFETY: FFI.
+        let channel = NonNull::new(unsafe { ibv_create_comp_channel(ctx.as_raw()) })
+            .ok_or_else(|| anyhow::anyhow!(io::Error::last_os_error()))?;
+
+        Ok(CompChannel {
+            inner: Arc::new(CompChannelInner {
+                channel,
+                ctx: ctx.clone(),
+            }),
+        })
+    }
+
+    /// Get the underlying `ibv_comp_channel` pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut ibv_comp_channel {
+        self.inner.channel.as_ptr()
+    }
+
+    /// Get the channel's file descriptor, for integration with an
+    /// epoll/tokio reactor instead of calling `wait` directly.
+    #[inline]
+    pub fn fd(&self) -> RawFd {
+        // SAFETY: the channel is valid for as long as `self` is alive.
+        unsafe { (*self.as_raw()).fd }
+    }
+
+    /// Block until a completion queue bound to this channel fires, returning
+    /// the CQ that did.
+    ///
+    /// The event is not acknowledged immediately -- acking takes the CQ's
+    /// lock on every call, so instead each call here only records the event
+    /// against the CQ, which flushes the whole accumulated batch in one
+    /// `ibv_ack_cq_events` call when the CQ is dropped.
+    ///
+    /// Callers must re-arm the returned CQ with `Cq::req_notify` and drain it
+    /// before calling `wait` again; see the type-level docs for the full
+    /// arm/drain/wait loop.
+    pub fn wait(&self) -> Result<Cq> {
+        let mut cq_ptr = ptr::null_mut();
+        let mut cq_context = ptr::null_mut();
+        // SAFETY: FFI.
+        let ret = unsafe { ibv_get_cq_event(self.as_raw(), &mut cq_ptr, &mut cq_context) };
+        if ret != 0 {
+            return Err(anyhow::anyhow!(io::Error::last_os_error()));
+        }
+
+        // SAFETY: `cq_context` is the `Weak<CqInner>` stashed by `Cq::new`
+        // when the CQ was bound to this channel.
+        let cq = unsafe { Cq::from_raw_with_context(cq_ptr, cq_context) };
+        cq.defer_ack();
+        Ok(cq)
+    }
+}
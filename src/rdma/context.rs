@@ -2,6 +2,7 @@ use std::ptr::NonNull;
 use std::sync::Arc;
 use std::{fmt, io, mem};
 
+use super::comp_channel::CompChannel;
 use super::cq::Cq;
 use super::device::*;
 use super::gid::Gid;
@@ -189,6 +190,13 @@ impl Context {
 
     /// Create a completion queue on this context.
     pub fn create_cq(&self, capacity: u32) -> Result<Cq> {
-        Cq::new(self.clone(), capacity)
+        Cq::new(self.clone(), capacity, None)
+    }
+
+    /// Create a completion queue on this context, bound to the given
+    /// completion channel so that completions can be waited on with
+    /// `CompChannel::wait` instead of polled.
+    pub fn create_cq_with_channel(&self, capacity: u32, channel: &CompChannel) -> Result<Cq> {
+        Cq::new(self.clone(), capacity, Some(channel))
     }
 }
@@ -0,0 +1,156 @@
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::{ffi, fmt, io, ptr};
+
+use super::comp_channel::CompChannel;
+use super::context::Context;
+use crate::bindings::*;
+use crate::utils::interop::*;
+use anyhow::Result;
+
+#[allow(dead_code)]
+struct CqInner {
+    cq: NonNull<ibv_cq>,
+    ctx: Context,
+
+    /// Kept alive so a channel-bound CQ doesn't outlive its channel.
+    channel: Option<CompChannel>,
+
+    /// Completion-channel events received but not yet acknowledged. Acking
+    /// takes the CQ's lock, so `CompChannel::wait` defers it and we flush
+    /// the whole batch in one `ibv_ack_cq_events` call right before destroy.
+    unacked_events: AtomicU64,
+}
+
+unsafe impl Send for CqInner {}
+unsafe impl Sync for CqInner {}
+
+impl fmt::Debug for CqInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cq").field("cq", &self.cq).finish()
+    }
+}
+
+impl Drop for CqInner {
+    fn drop(&mut self) {
+        let pending = self.unacked_events.load(Ordering::Relaxed);
+        if pending > 0 {
+            // SAFETY: FFI; every completion-channel event must be
+            // acknowledged before the CQ is destroyed.
+            unsafe { ibv_ack_cq_events(self.cq.as_ptr(), pending as u32) };
+        }
+        // SAFETY: a non-null `cq_context` is the one `Weak<CqInner>` that
+        // `Cq::new` stashed for a channel-bound CQ; reclaim it so the
+        // allocation is freed along with the CQ.
+        let stashed = unsafe { (*self.cq.as_ptr()).cq_context };
+        if !stashed.is_null() {
+            drop(unsafe { Weak::from_raw(stashed as *const CqInner) });
+        }
+        // SAFETY: FFI.
+        unsafe { ibv_destroy_cq(self.cq.as_ptr()) };
+    }
+}
+
+/// Completion queue.
+///
+/// This type is a simple wrapper of an `Arc` and is guaranteed to have the
+/// same memory layout with it.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct Cq {
+    inner: Arc<CqInner>,
+}
+
+impl Cq {
+    /// Create a completion queue on `ctx`, optionally bound to a completion
+    /// channel so completions can be waited on instead of polled.
+    pub(crate) fn new(ctx: Context, capacity: u32, channel: Option<&CompChannel>) -> Result<Self> {
+        let channel_ptr = channel.map_or(ptr::null_mut(), |c| c.as_raw());
+
+        // SAFETY: FFI. `cq_context` is left null here; it is only filled in
+        // below for channel-bound CQs, once we know the address of the `Arc`
+        // we're about to stash a `Weak` reference to.
+        let cq = NonNull::new(unsafe {
+            ibv_create_cq(ctx.as_raw(), capacity as i32, ptr::null_mut(), channel_ptr, 0)
+        })
+        .ok_or_else(|| anyhow::anyhow!(io::Error::last_os_error()))?;
+
+        let inner = Arc::new(CqInner {
+            cq,
+            ctx,
+            channel: channel.cloned(),
+            unacked_events: AtomicU64::new(0),
+        });
+
+        if channel.is_some() {
+            // Stash a *weak* reference inside the `ibv_cq` itself, so that
+            // `ibv_get_cq_event` can hand it back to `CompChannel::wait` and
+            // let it reconstruct a `Cq` that shares ownership with this one,
+            // without the stash itself keeping the CQ alive forever (a
+            // strong reference here would mean `CqInner::drop` -- and so
+            // `ibv_destroy_cq` -- never runs). Only channel-bound CQs need
+            // this; a polled-only CQ's `cq_context` is left null. See
+            // `from_raw_with_context`.
+            let stashed = Arc::downgrade(&inner).into_raw() as *mut ffi::c_void;
+            // SAFETY: `cq` was just created above and isn't shared with any
+            // other thread yet.
+            unsafe { (*cq.as_ptr()).cq_context = stashed };
+        }
+
+        Ok(Cq { inner })
+    }
+
+    /// Get the underlying `ibv_cq` pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut ibv_cq {
+        self.inner.cq.as_ptr()
+    }
+
+    /// Arm this CQ for one-shot completion-channel notification.
+    ///
+    /// Must be re-armed after every `CompChannel::wait` that returns this
+    /// CQ. If `solicited_only` is set, only work completions posted with the
+    /// solicited flag will trigger a notification.
+    pub fn req_notify(&self, solicited_only: bool) -> Result<()> {
+        // SAFETY: FFI.
+        let ret = unsafe { ibv_req_notify_cq(self.as_raw(), solicited_only as i32) };
+        from_c_err(ret)
+    }
+
+    /// Record that the completion channel delivered one event for this CQ,
+    /// deferring the `ibv_ack_cq_events` call until the CQ is destroyed.
+    pub(crate) fn defer_ack(&self) {
+        self.inner.unacked_events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Reconstruct the `Cq` handle that owns `cq_ptr`, from the opaque
+    /// `cq_context` pointer `ibv_get_cq_event` handed back -- the
+    /// `Weak<CqInner>` raw pointer `Cq::new` stashed in `ibv_cq::cq_context`.
+    ///
+    /// # Safety
+    /// `cq_context` must be exactly the pointer `Cq::new` stashed for the CQ
+    /// that owns `cq_ptr`, i.e. obtained from a `Cq` created via
+    /// `Context::create_cq_with_channel`. Calling this with any other
+    /// pointer is undefined behavior.
+    pub(crate) unsafe fn from_raw_with_context(
+        _cq_ptr: *mut ibv_cq,
+        cq_context: *mut ffi::c_void,
+    ) -> Cq {
+        // SAFETY: caller guarantees `cq_context` is a live stashed `Weak`
+        // pointer.
+        let weak = unsafe { Weak::from_raw(cq_context as *const CqInner) };
+        // The event can only have fired on a CQ that is still alive -- the
+        // caller's own `Cq` handles keep it that way across
+        // the `ibv_get_cq_event` call -- so the upgrade can't fail.
+        let inner = weak
+            .upgrade()
+            .expect("CQ dropped while a completion-channel event was still pending");
+        // Hand the `Weak` back to the stash so it's still valid the next
+        // time an event fires on this CQ. This keeps the allocation's weak
+        // count (but not `CqInner` itself) alive for the CQ's lifetime,
+        // same as any other permanently-held `Weak`.
+        let _ = Weak::into_raw(weak);
+        Cq { inner }
+    }
+}
@@ -0,0 +1,280 @@
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::{fmt, io, mem, slice};
+
+use super::qp::Qp;
+use crate::bindings::*;
+use anyhow::Result;
+
+#[allow(dead_code)]
+struct FlowInner {
+    flow: NonNull<ibv_flow>,
+
+    /// Keep the QP alive for as long as the flow is attached to it: detaching
+    /// via `ibv_destroy_flow` after the QP (and its context) is gone would be
+    /// a use-after-free.
+    qp: Qp,
+}
+
+unsafe impl Send for FlowInner {}
+unsafe impl Sync for FlowInner {}
+
+impl fmt::Debug for FlowInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Flow").field("flow", &self.flow).finish()
+    }
+}
+
+impl Drop for FlowInner {
+    fn drop(&mut self) {
+        // SAFETY: FFI.
+        unsafe { ibv_destroy_flow(self.flow.as_ptr()) };
+    }
+}
+
+/// A flow-steering rule attached to a QP via `ibv_create_flow`.
+///
+/// Detaches itself (`ibv_destroy_flow`) on drop.
+///
+/// This type is a simple wrapper of an `Arc` and is guaranteed to have the
+/// same memory layout with it.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct Flow {
+    inner: Arc<FlowInner>,
+}
+
+/// Whether a flow matches normal traffic or is a catch-all sniffer rule.
+///
+/// Maps to `ibv_flow_attr_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowKind {
+    /// Steer packets that match the given specs.
+    Normal,
+    /// Steer all traffic not claimed by a more specific normal rule.
+    AllDefault,
+    /// Mirror matching traffic to this QP without consuming it.
+    Sniffer,
+}
+
+impl FlowKind {
+    fn as_raw(self) -> ibv_flow_attr_type::Type {
+        match self {
+            FlowKind::Normal => ibv_flow_attr_type::IBV_FLOW_ATTR_NORMAL,
+            FlowKind::AllDefault => ibv_flow_attr_type::IBV_FLOW_ATTR_ALL_DEFAULT,
+            FlowKind::Sniffer => ibv_flow_attr_type::IBV_FLOW_ATTR_SNIFFER,
+        }
+    }
+}
+
+/// A single packed `ibv_flow_spec_*` struct, ready to be appended to an
+/// `ibv_flow_attr`.
+enum Spec {
+    Eth(ibv_flow_spec_eth),
+    Ipv4(ibv_flow_spec_ipv4),
+    Ipv6(ibv_flow_spec_ipv6),
+    TcpUdp(ibv_flow_spec_tcp_udp),
+}
+
+impl Spec {
+    /// View this spec as the raw bytes `ibv_create_flow` expects to find
+    /// packed back-to-back after the `ibv_flow_attr` header.
+    fn as_bytes(&self) -> &[u8] {
+        // SAFETY: all `ibv_flow_spec_*` structs are `repr(C)` and begin with
+        // the common `ibv_flow_spec_hdr` (type, size), which is exactly what
+        // `ibv_create_flow` walks over.
+        match self {
+            Spec::Eth(s) => unsafe {
+                slice::from_raw_parts((s as *const _) as *const u8, mem::size_of_val(s))
+            },
+            Spec::Ipv4(s) => unsafe {
+                slice::from_raw_parts((s as *const _) as *const u8, mem::size_of_val(s))
+            },
+            Spec::Ipv6(s) => unsafe {
+                slice::from_raw_parts((s as *const _) as *const u8, mem::size_of_val(s))
+            },
+            Spec::TcpUdp(s) => unsafe {
+                slice::from_raw_parts((s as *const _) as *const u8, mem::size_of_val(s))
+            },
+        }
+    }
+}
+
+/// Builder for an `ibv_flow_attr` plus its packed per-layer spec structs.
+///
+/// Specs are appended in the order they are added and packed back-to-back
+/// after the header, exactly as `ibv_create_flow` expects. Call `attach` to
+/// create the flow on a QP.
+pub struct FlowBuilder {
+    priority: u16,
+    kind: FlowKind,
+    port: u8,
+    specs: Vec<Spec>,
+}
+
+impl FlowBuilder {
+    /// Start building a flow rule on the given physical port.
+    pub fn new(port: u8) -> Self {
+        Self {
+            priority: 0,
+            kind: FlowKind::Normal,
+            port,
+            specs: Vec::new(),
+        }
+    }
+
+    /// Set the priority group this rule competes in; lower values match
+    /// first.
+    pub fn priority(mut self, priority: u16) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the flow type (normal vs. sniffer/all-default).
+    pub fn kind(mut self, kind: FlowKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Match on Ethernet header fields, given the value to match and the
+    /// mask of bits to compare.
+    pub fn eth(mut self, val: ibv_flow_eth_filter, mask: ibv_flow_eth_filter) -> Self {
+        self.specs.push(Spec::Eth(ibv_flow_spec_eth {
+            type_: ibv_flow_spec_type::IBV_FLOW_SPEC_ETH,
+            size: mem::size_of::<ibv_flow_spec_eth>() as u16,
+            val,
+            mask,
+        }));
+        self
+    }
+
+    /// Match on IPv4 header fields, given the value to match and the mask of
+    /// bits to compare.
+    pub fn ipv4(mut self, val: ibv_flow_ipv4_filter, mask: ibv_flow_ipv4_filter) -> Self {
+        self.specs.push(Spec::Ipv4(ibv_flow_spec_ipv4 {
+            type_: ibv_flow_spec_type::IBV_FLOW_SPEC_IPV4,
+            size: mem::size_of::<ibv_flow_spec_ipv4>() as u16,
+            val,
+            mask,
+        }));
+        self
+    }
+
+    /// Match on IPv6 header fields, given the value to match and the mask of
+    /// bits to compare.
+    pub fn ipv6(mut self, val: ibv_flow_ipv6_filter, mask: ibv_flow_ipv6_filter) -> Self {
+        self.specs.push(Spec::Ipv6(ibv_flow_spec_ipv6 {
+            type_: ibv_flow_spec_type::IBV_FLOW_SPEC_IPV6,
+            size: mem::size_of::<ibv_flow_spec_ipv6>() as u16,
+            val,
+            mask,
+        }));
+        self
+    }
+
+    /// Match on TCP or UDP header fields, given the value to match and the
+    /// mask of bits to compare.
+    pub fn tcp_udp(
+        mut self,
+        is_tcp: bool,
+        val: ibv_flow_tcp_udp_filter,
+        mask: ibv_flow_tcp_udp_filter,
+    ) -> Self {
+        let type_ = if is_tcp {
+            ibv_flow_spec_type::IBV_FLOW_SPEC_TCP
+        } else {
+            ibv_flow_spec_type::IBV_FLOW_SPEC_UDP
+        };
+        self.specs.push(Spec::TcpUdp(ibv_flow_spec_tcp_udp {
+            type_,
+            size: mem::size_of::<ibv_flow_spec_tcp_udp>() as u16,
+            val,
+            mask,
+        }));
+        self
+    }
+
+    /// Pack the `ibv_flow_attr` header and its specs into a single buffer,
+    /// back-to-back, exactly as `ibv_create_flow` expects to find them.
+    fn build_buf(&self) -> Vec<u8> {
+        let spec_bytes: Vec<u8> = self.specs.iter().flat_map(|s| s.as_bytes().to_vec()).collect();
+
+        let mut buf = vec![0u8; mem::size_of::<ibv_flow_attr>() + spec_bytes.len()];
+        // SAFETY: POD type, zero-initialized before use.
+        let mut header = unsafe { mem::zeroed::<ibv_flow_attr>() };
+        header.type_ = self.kind.as_raw();
+        header.size = buf.len() as u16;
+        header.priority = self.priority;
+        header.num_of_specs = self.specs.len() as u8;
+        header.port = self.port;
+        header.flags = 0;
+
+        // SAFETY: `header` is `repr(C)` and POD; reinterpreting it as bytes
+        // to pack it into `buf` ahead of the specs is exactly what
+        // `ibv_create_flow` expects to find.
+        let header_bytes = unsafe {
+            slice::from_raw_parts((&header as *const _) as *const u8, mem::size_of_val(&header))
+        };
+        buf[..header_bytes.len()].copy_from_slice(header_bytes);
+        buf[header_bytes.len()..].copy_from_slice(&spec_bytes);
+        buf
+    }
+
+    /// Assemble the `ibv_flow_attr` and its specs, and attach the resulting
+    /// flow rule to `qp`.
+    pub fn attach(self, qp: &Qp) -> Result<Flow> {
+        let mut buf = self.build_buf();
+
+        // SAFETY: FFI; `buf` holds a valid `ibv_flow_attr` followed by its
+        // packed specs, matching what `ibv_create_flow` expects. A provider
+        // that doesn't support flow steering returns `NULL` with `errno` set
+        // to `ENOSYS` by the FFI binding (see `bindings::mlnx4`).
+        let flow = NonNull::new(unsafe {
+            ibv_create_flow(qp.as_raw(), buf.as_mut_ptr() as *mut ibv_flow_attr)
+        })
+        .ok_or_else(|| anyhow::anyhow!(io::Error::last_os_error()))?;
+
+        Ok(Flow {
+            inner: Arc::new(FlowInner {
+                flow,
+                qp: qp.clone(),
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_buf_packs_header_and_specs_back_to_back() {
+        let eth_val = unsafe { mem::zeroed::<ibv_flow_eth_filter>() };
+        let eth_mask = unsafe { mem::zeroed::<ibv_flow_eth_filter>() };
+        let builder = FlowBuilder::new(1)
+            .priority(3)
+            .kind(FlowKind::Normal)
+            .eth(eth_val, eth_mask);
+
+        let buf = builder.build_buf();
+
+        assert_eq!(
+            buf.len(),
+            mem::size_of::<ibv_flow_attr>() + mem::size_of::<ibv_flow_spec_eth>()
+        );
+
+        // SAFETY: `buf` was just packed by `build_buf` and is large enough
+        // to hold the header it claims to hold.
+        let header = unsafe { &*(buf.as_ptr() as *const ibv_flow_attr) };
+        assert_eq!(header.size as usize, buf.len());
+        assert_eq!(header.priority, 3);
+        assert_eq!(header.num_of_specs, 1);
+        assert_eq!(header.port, 1);
+    }
+
+    #[test]
+    fn build_buf_with_no_specs_is_header_only() {
+        let buf = FlowBuilder::new(1).build_buf();
+        assert_eq!(buf.len(), mem::size_of::<ibv_flow_attr>());
+    }
+}
@@ -0,0 +1,90 @@
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::{fmt, io};
+
+use super::mr::MrSlice;
+use super::pd::Pd;
+use crate::bindings::*;
+use anyhow::Result;
+
+#[allow(dead_code)]
+struct MwInner {
+    mw: NonNull<ibv_mw>,
+
+    /// Keep the PD alive for as long as the window lives.
+    pd: Pd,
+}
+
+unsafe impl Send for MwInner {}
+unsafe impl Sync for MwInner {}
+
+impl fmt::Debug for MwInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mw").field("mw", &self.mw).finish()
+    }
+}
+
+impl Drop for MwInner {
+    fn drop(&mut self) {
+        // SAFETY: FFI.
+        unsafe { ibv_dealloc_mw(self.mw.as_ptr()) };
+    }
+}
+
+/// Memory window.
+///
+/// A memory window lets an application hand a remote peer a revocable,
+/// narrowly-scoped rkey instead of exposing a whole MR's rkey. Type-1
+/// windows are bound with `ibv_bind_mw`; type-2 windows (`IBV_MW_TYPE_2`) are
+/// bound by posting a `SendWrDetails::BindMw` work request to the send
+/// queue, and can be invalidated with `IBV_WR_LOCAL_INV`.
+///
+/// This type is a simple wrapper of an `Arc` and is guaranteed to have the
+/// same memory layout with it.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct Mw {
+    inner: Arc<MwInner>,
+}
+
+impl Mw {
+    /// Allocate a memory window of the given type on a protection domain.
+    pub fn alloc(pd: &Pd, ty: ibv_mw_type::Type) -> Result<Self> {
+        // SAFETY: FFI.
+        let mw = NonNull::new(unsafe { ibv_alloc_mw(pd.as_raw(), ty) })
+            .ok_or_else(|| anyhow::anyhow!(io::Error::last_os_error()))?;
+
+        Ok(Mw {
+            inner: Arc::new(MwInner {
+                mw,
+                pd: pd.clone(),
+            }),
+        })
+    }
+
+    /// Get the underlying `ibv_mw` pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut ibv_mw {
+        self.inner.mw.as_ptr()
+    }
+
+    /// Get the window's current rkey.
+    ///
+    /// For a type-2 window this is only valid after a successful bind; the
+    /// rkey actually handed out to a remote peer is the one passed to
+    /// `SendWrDetails::BindMw`, not necessarily this value.
+    #[inline]
+    pub fn rkey(&self) -> u32 {
+        // SAFETY: the window is valid for as long as `self` is alive.
+        unsafe { (*self.as_raw()).rkey }
+    }
+}
+
+/// Parameters describing the memory region a type-2 memory window should be
+/// bound to, used by `SendWrDetails::BindMw`.
+pub struct MwBindInfo<'a> {
+    /// The local memory area the window will grant access to.
+    pub mr: MrSlice<'a>,
+    /// Access flags granted to the remote peer holding the window's rkey.
+    pub access: ibv_access_flags,
+}
@@ -0,0 +1,211 @@
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::{fmt, io, mem, ptr};
+
+use super::cq::Cq;
+use super::mr::MrSlice;
+use super::pd::Pd;
+use super::srq::Srq;
+use super::xrcd::Xrcd;
+use crate::bindings::*;
+use anyhow::Result;
+
+/// Build a scatter-gather list out of a set of local memory slices.
+pub(crate) fn build_sgl(local: &[MrSlice]) -> Vec<ibv_sge> {
+    local.iter().map(MrSlice::to_sge).collect()
+}
+
+/// Address of a remote QP, as required to `SendTo` a UD send work request.
+#[derive(Debug, Clone, Copy)]
+pub struct QpPeer {
+    pub ah: *mut ibv_ah,
+    pub qp_num: u32,
+    pub qkey: u32,
+}
+
+impl From<&QpPeer> for ud_t {
+    fn from(peer: &QpPeer) -> Self {
+        ud_t {
+            ah: peer.ah,
+            remote_qpn: peer.qp_num,
+            remote_qkey: peer.qkey,
+        }
+    }
+}
+
+/// Queue pair type.
+///
+/// `XrcIni`/`XrcTgt` are the send-side and receive-side halves of an XRC
+/// connection: see the `xrcd` module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QpType {
+    RC,
+    UC,
+    UD,
+    XrcIni,
+    XrcTgt,
+}
+
+impl QpType {
+    fn as_raw(self) -> ibv_qp_type::Type {
+        match self {
+            QpType::RC => ibv_qp_type::IBV_QPT_RC,
+            QpType::UC => ibv_qp_type::IBV_QPT_UC,
+            QpType::UD => ibv_qp_type::IBV_QPT_UD,
+            QpType::XrcIni => ibv_qp_type::IBV_QPT_XRC_SEND,
+            QpType::XrcTgt => ibv_qp_type::IBV_QPT_XRC_RECV,
+        }
+    }
+}
+
+#[allow(dead_code)]
+struct QpInner {
+    qp: NonNull<ibv_qp>,
+
+    /// `None` for a `QpType::XrcTgt` QP, which is created directly on an
+    /// `Xrcd` and has no PD of its own.
+    pd: Option<Pd>,
+
+    /// `None` for a `QpType::XrcTgt` QP: XRC TGT QPs generate no
+    /// completions of their own (the XRC SRQ's CQ receives them instead).
+    scq: Option<Cq>,
+    rcq: Option<Cq>,
+
+    /// Kept alive so a SRQ this QP shares its receive queue with isn't
+    /// destroyed out from under it.
+    srq: Option<Srq>,
+
+    /// Kept alive for a `QpType::XrcTgt` QP, which is created on this domain
+    /// rather than a PD.
+    xrcd: Option<Xrcd>,
+}
+
+unsafe impl Send for QpInner {}
+unsafe impl Sync for QpInner {}
+
+impl fmt::Debug for QpInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Qp").field("qp", &self.qp).finish()
+    }
+}
+
+impl Drop for QpInner {
+    fn drop(&mut self) {
+        // SAFETY: FFI.
+        unsafe { ibv_destroy_qp(self.qp.as_ptr()) };
+    }
+}
+
+/// Queue pair.
+///
+/// This type is a simple wrapper of an `Arc` and is guaranteed to have the
+/// same memory layout with it.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct Qp {
+    inner: Arc<QpInner>,
+}
+
+impl Qp {
+    /// Create a queue pair on `pd`.
+    ///
+    /// Pass `srq` to have this QP pull receive buffers from a shared
+    /// receive queue instead of carrying its own -- essential for scaling
+    /// incast/all-to-all patterns, where per-QP receive buffers blow up
+    /// memory. A QP created with a SRQ must not have `RecvWr`s posted to it
+    /// directly; post to the SRQ instead.
+    ///
+    /// `QpType::XrcIni` is created through this same path (it is a regular
+    /// `ibv_create_qp` call with `IBV_QPT_XRC_SEND`); `QpType::XrcTgt` is
+    /// not, since it has no PD or completion queues of its own -- see
+    /// `create_xrc_tgt`.
+    pub fn create(
+        pd: &Pd,
+        qp_type: QpType,
+        scq: &Cq,
+        rcq: &Cq,
+        cap: ibv_qp_cap,
+        srq: Option<&Srq>,
+    ) -> Result<Self> {
+        // SAFETY: will be filled below.
+        let mut attr = unsafe { mem::zeroed::<ibv_qp_init_attr>() };
+        attr.send_cq = scq.as_raw();
+        attr.recv_cq = rcq.as_raw();
+        attr.cap = cap;
+        attr.qp_type = qp_type.as_raw();
+        attr.srq = srq.map_or(ptr::null_mut(), Srq::as_raw);
+
+        // SAFETY: FFI.
+        let qp = NonNull::new(unsafe { ibv_create_qp(pd.as_raw(), &mut attr) })
+            .ok_or_else(|| anyhow::anyhow!(io::Error::last_os_error()))?;
+
+        Ok(Qp {
+            inner: Arc::new(QpInner {
+                qp,
+                pd: Some(pd.clone()),
+                scq: Some(scq.clone()),
+                rcq: Some(rcq.clone()),
+                srq: srq.cloned(),
+                xrcd: None,
+            }),
+        })
+    }
+
+    /// Create an XRC TGT (receive-side) queue pair on `xrcd`.
+    ///
+    /// Unlike every other QP type, a `QpType::XrcTgt` QP has no PD and no
+    /// completion queues of its own: incoming sends land in `srq` and
+    /// complete on the CQ `srq` was created with (via `Srq::new_xrc`). It
+    /// exists only so a remote `QpType::XrcIni` QP has something to connect
+    /// to; applications never post work requests to it directly.
+    pub fn create_xrc_tgt(xrcd: &Xrcd, srq: &Srq) -> Result<Self> {
+        // SAFETY: will be filled below.
+        let mut attr = unsafe { mem::zeroed::<ibv_qp_init_attr_ex>() };
+        attr.qp_type = QpType::XrcTgt.as_raw();
+        attr.comp_mask = ibv_qp_init_attr_mask::IBV_QP_INIT_ATTR_XRCD.0;
+        attr.xrcd = xrcd.as_raw();
+        attr.srq = srq.as_raw();
+
+        // SAFETY: FFI.
+        let qp = NonNull::new(unsafe { ibv_create_qp_ex(xrcd.context().as_raw(), &mut attr) })
+            .ok_or_else(|| anyhow::anyhow!(io::Error::last_os_error()))?;
+
+        Ok(Qp {
+            inner: Arc::new(QpInner {
+                qp,
+                pd: None,
+                scq: None,
+                rcq: None,
+                srq: Some(srq.clone()),
+                xrcd: Some(xrcd.clone()),
+            }),
+        })
+    }
+
+    /// Get the underlying `ibv_qp` pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut ibv_qp {
+        self.inner.qp.as_ptr()
+    }
+
+    /// Get the send completion queue this QP was created with, if it has
+    /// one (a `QpType::XrcTgt` QP does not: see `create_xrc_tgt`).
+    #[inline]
+    pub fn scq(&self) -> Option<&Cq> {
+        self.inner.scq.as_ref()
+    }
+
+    /// Get the receive completion queue this QP was created with, if it has
+    /// one (a `QpType::XrcTgt` QP does not: see `create_xrc_tgt`).
+    #[inline]
+    pub fn rcq(&self) -> Option<&Cq> {
+        self.inner.rcq.as_ref()
+    }
+
+    /// Get the shared receive queue this QP pulls receive buffers from, if
+    /// it was created with one.
+    #[inline]
+    pub fn srq(&self) -> Option<&Srq> {
+        self.inner.srq.as_ref()
+    }
+}
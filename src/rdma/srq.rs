@@ -0,0 +1,211 @@
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::{fmt, io, mem, ptr};
+
+use super::cq::Cq;
+use super::pd::Pd;
+use super::wr::RecvWr;
+use super::xrcd::Xrcd;
+use crate::bindings::*;
+use crate::utils::interop::*;
+use anyhow::Result;
+
+#[allow(dead_code)]
+struct SrqInner {
+    srq: NonNull<ibv_srq>,
+
+    /// Keep the PD alive for as long as the SRQ lives.
+    pd: Pd,
+
+    /// Keep the domain alive for as long as an XRC SRQ lives.
+    xrcd: Option<Xrcd>,
+}
+
+unsafe impl Send for SrqInner {}
+unsafe impl Sync for SrqInner {}
+
+impl fmt::Debug for SrqInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Srq").field("srq", &self.srq).finish()
+    }
+}
+
+impl Drop for SrqInner {
+    fn drop(&mut self) {
+        // SAFETY: FFI.
+        unsafe { ibv_destroy_srq(self.srq.as_ptr()) };
+    }
+}
+
+/// Shared receive queue.
+///
+/// A SRQ lets many queue pairs share a single pool of receive buffers,
+/// instead of every QP carrying its own. This is essential for scaling
+/// incast / all-to-all connection patterns, where giving each QP its own
+/// receive queue blows up memory with the number of peers.
+///
+/// This type is a simple wrapper of an `Arc` and is guaranteed to have the
+/// same memory layout with it.
+///
+/// A QP opts into sharing a SRQ by being created with it attached, via
+/// `Qp::create`'s `srq` parameter.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct Srq {
+    inner: Arc<SrqInner>,
+}
+
+impl Srq {
+    /// Create a shared receive queue on the given protection domain.
+    ///
+    /// `max_wr` and `max_sge` bound the capacity of the queue. `srq_limit`
+    /// sets the low-watermark work-request count that triggers an
+    /// `IBV_EVENT_SRQ_LIMIT_REACHED` asynchronous event; pass `0` to disable
+    /// the limit event.
+    pub fn new(pd: &Pd, max_wr: u32, max_sge: u32, srq_limit: u32) -> Result<Self> {
+        // SAFETY: POD type, zero-initialized before use.
+        let mut attr = unsafe { mem::zeroed::<ibv_srq_init_attr>() };
+        attr.attr.max_wr = max_wr;
+        attr.attr.max_sge = max_sge;
+        attr.attr.srq_limit = srq_limit;
+
+        // SAFETY: FFI.
+        let srq = NonNull::new(unsafe { ibv_create_srq(pd.as_raw(), &mut attr) })
+            .ok_or_else(|| anyhow::anyhow!(io::Error::last_os_error()))?;
+
+        Ok(Srq {
+            inner: Arc::new(SrqInner {
+                srq,
+                pd: pd.clone(),
+                xrcd: None,
+            }),
+        })
+    }
+
+    /// Create an XRC shared receive queue: a SRQ that a `QpType::XrcTgt` QP
+    /// routes incoming sends into, and that a single node-wide domain can
+    /// therefore serve to many remote `QpType::XrcIni` senders without a
+    /// dedicated receive queue per sender.
+    ///
+    /// `cq` receives the completions for messages landing in this SRQ (XRC
+    /// SRQs always need one, unlike the basic SRQ created by `new`).
+    pub fn new_xrc(
+        pd: &Pd,
+        xrcd: &Xrcd,
+        cq: &Cq,
+        max_wr: u32,
+        max_sge: u32,
+        srq_limit: u32,
+    ) -> Result<Self> {
+        // SAFETY: POD type, zero-initialized before use.
+        let mut attr = unsafe { mem::zeroed::<ibv_srq_init_attr_ex>() };
+        attr.attr.max_wr = max_wr;
+        attr.attr.max_sge = max_sge;
+        attr.attr.srq_limit = srq_limit;
+        attr.srq_type = ibv_srq_type::IBV_SRQT_XRC;
+        attr.comp_mask = ibv_srq_init_attr_mask::IBV_SRQ_INIT_ATTR_TYPE.0
+            | ibv_srq_init_attr_mask::IBV_SRQ_INIT_ATTR_PD.0
+            | ibv_srq_init_attr_mask::IBV_SRQ_INIT_ATTR_XRCD.0
+            | ibv_srq_init_attr_mask::IBV_SRQ_INIT_ATTR_CQ.0;
+        attr.pd = pd.as_raw();
+        attr.xrcd = xrcd.as_raw();
+        attr.cq = cq.as_raw();
+
+        // SAFETY: FFI.
+        let srq = NonNull::new(unsafe { ibv_create_srq_ex(xrcd.context().as_raw(), &mut attr) })
+            .ok_or_else(|| anyhow::anyhow!(io::Error::last_os_error()))?;
+
+        Ok(Srq {
+            inner: Arc::new(SrqInner {
+                srq,
+                pd: pd.clone(),
+                xrcd: Some(xrcd.clone()),
+            }),
+        })
+    }
+
+    /// Get the underlying `ibv_srq` pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut ibv_srq {
+        self.inner.srq.as_ptr()
+    }
+
+    /// Get this SRQ's number, as handed to a remote peer's `QpType::XrcIni`
+    /// QP so its sends land here.
+    pub fn srq_num(&self) -> Result<u32> {
+        let mut num = 0;
+        // SAFETY: FFI.
+        let ret = unsafe { ibv_get_srq_num(self.as_raw(), &mut num) };
+        from_c_err(ret)?;
+        Ok(num)
+    }
+
+    /// Post a batch of receive work requests to this SRQ.
+    ///
+    /// Unlike posting to a QP's own receive queue, buffers posted here may be
+    /// consumed by completions on any QP that was created with this SRQ
+    /// attached, not necessarily the QP the application expects.
+    pub fn post_recv(&self, wrs: &[RecvWr]) -> Result<()> {
+        if wrs.is_empty() {
+            return Ok(());
+        }
+
+        // SAFETY: the resulting `ibv_recv_wr`s borrow their SGEs from `wrs`,
+        // which outlives this call; we only link and post them below.
+        let mut raw_wrs: Vec<ibv_recv_wr> = wrs.iter().map(|wr| unsafe { wr.to_wr() }).collect();
+        link_wrs(&mut raw_wrs);
+
+        let mut bad_wr = ptr::null_mut();
+        // SAFETY: FFI.
+        let ret = unsafe { ibv_post_srq_recv(self.as_raw(), raw_wrs.as_mut_ptr(), &mut bad_wr) };
+        from_c_err(ret)
+    }
+}
+
+/// Chain a batch of `ibv_recv_wr`s together through their `next` pointers,
+/// exactly as `ibv_post_srq_recv` expects a linked list. The last entry's
+/// `next` is left alone (callers zero-init it via `RecvWr::to_wr`).
+fn link_wrs(wrs: &mut [ibv_recv_wr]) {
+    for i in 0..wrs.len().saturating_sub(1) {
+        let next = &mut wrs[i + 1] as *mut _;
+        wrs[i].next = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wr(wr_id: u64) -> ibv_recv_wr {
+        ibv_recv_wr {
+            wr_id,
+            next: ptr::null_mut(),
+            sg_list: ptr::null_mut(),
+            num_sge: 0,
+        }
+    }
+
+    #[test]
+    fn link_wrs_chains_in_order() {
+        let mut wrs = vec![wr(0), wr(1), wr(2)];
+        link_wrs(&mut wrs);
+
+        // SAFETY: the pointers were just set to point within `wrs`, which
+        // is still alive and hasn't moved.
+        unsafe {
+            assert_eq!((*wrs[0].next).wr_id, 1);
+            assert_eq!((*wrs[1].next).wr_id, 2);
+        }
+        assert!(wrs[2].next.is_null());
+    }
+
+    #[test]
+    fn link_wrs_handles_single_and_empty_batches() {
+        let mut single = vec![wr(0)];
+        link_wrs(&mut single);
+        assert!(single[0].next.is_null());
+
+        let mut empty: Vec<ibv_recv_wr> = vec![];
+        link_wrs(&mut empty);
+    }
+}
@@ -4,6 +4,7 @@ use std::{mem, ptr};
 use rdma_sys::*;
 
 use super::mr::*;
+use super::mw::{Mw, MwBindInfo};
 use super::qp::{build_sgl, QpPeer};
 
 /// Wrapper of basic parameters of a RDMA work request.
@@ -37,6 +38,52 @@ pub enum SendWrDetails<'a> {
 
     /// Write requires a remote memory area to write to and an optional immediate.
     Write(&'a RemoteMrSlice<'a>, Option<u32>),
+
+    /// Compare-and-swap atomically compares the remote 8 bytes against
+    /// `compare` and, if equal, replaces them with `swap`. The local SGL
+    /// receives the value that was in remote memory before the operation.
+    CompareSwap(&'a RemoteMrSlice<'a>, u64, u64),
+
+    /// Fetch-and-add atomically adds `add` to the remote 8 bytes. The local
+    /// SGL receives the value that was in remote memory before the addition.
+    FetchAdd(&'a RemoteMrSlice<'a>, u64),
+
+    /// Bind a type-2 memory window to `bind_info`'s memory area, handing it
+    /// the given `rkey`.
+    BindMw {
+        mw: &'a Mw,
+        rkey: u32,
+        bind_info: MwBindInfo<'a>,
+    },
+}
+
+/// Check the hard verbs invariants for atomic operations: exactly one SGE of
+/// exactly 8 bytes, targeting an 8-byte-aligned remote address.
+///
+/// # Panics
+///
+/// Panics if any invariant is violated. This is checked unconditionally
+/// (not only in debug builds) because a mis-sized or misaligned atomic
+/// silently produces wrong data instead of failing loudly.
+///
+/// `to_wr` has no `Result` to report through -- it's an infallible
+/// transform into the raw `ibv_send_wr` that `ibv_post_send` posts -- so a
+/// panic is the only way to surface a caller bug here rather than silently
+/// building a malformed work request. If `to_wr` is ever made fallible,
+/// this should return an error instead so one bad WR doesn't abort a whole
+/// batch build.
+fn validate_atomic_sgl(sgl: &[ibv_sge], remote_addr: u64) {
+    assert_eq!(sgl.len(), 1, "atomic operations take exactly one SGE");
+    assert_eq!(
+        sgl[0].length,
+        mem::size_of::<u64>() as u32,
+        "atomic operations act on exactly 8 bytes"
+    );
+    assert_eq!(
+        remote_addr % mem::size_of::<u64>() as u64,
+        0,
+        "atomic operations require an 8-byte-aligned remote address"
+    );
 }
 
 /// Send work request.
@@ -130,6 +177,43 @@ impl<'a> SendWr<'a> {
                     ibv_wr_opcode::IBV_WR_RDMA_WRITE_WITH_IMM,
                 );
             }
+            SendWrDetails::CompareSwap(remote, compare, swap) => {
+                validate_atomic_sgl(&self.0.local, remote.addr());
+                wr.wr.atomic = atomic_t {
+                    remote_addr: remote.addr(),
+                    rkey: remote.rkey(),
+                    compare_add: *compare,
+                    swap: *swap,
+                };
+                wr.opcode = ibv_wr_opcode::IBV_WR_ATOMIC_CMP_AND_SWP;
+            }
+            SendWrDetails::FetchAdd(remote, add) => {
+                validate_atomic_sgl(&self.0.local, remote.addr());
+                wr.wr.atomic = atomic_t {
+                    remote_addr: remote.addr(),
+                    rkey: remote.rkey(),
+                    compare_add: *add,
+                    swap: 0,
+                };
+                wr.opcode = ibv_wr_opcode::IBV_WR_ATOMIC_FETCH_AND_ADD;
+            }
+            SendWrDetails::BindMw {
+                mw,
+                rkey,
+                bind_info,
+            } => {
+                wr.bind_mw = bind_mw_t {
+                    mw: mw.as_raw(),
+                    rkey: *rkey,
+                    bind_info: ibv_mw_bind_info {
+                        mr: bind_info.mr.as_raw_mr(),
+                        addr: bind_info.mr.addr(),
+                        length: bind_info.mr.len() as u64,
+                        mw_access_flags: bind_info.access.0,
+                    },
+                };
+                wr.opcode = ibv_wr_opcode::IBV_WR_BIND_MW;
+            }
         };
 
         wr
@@ -174,3 +258,39 @@ impl<'a> RecvWr<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sge(length: u32) -> ibv_sge {
+        ibv_sge {
+            addr: 0,
+            length,
+            lkey: 0,
+        }
+    }
+
+    #[test]
+    fn validate_atomic_sgl_accepts_single_aligned_8_byte_sge() {
+        validate_atomic_sgl(&[sge(8)], 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "exactly one SGE")]
+    fn validate_atomic_sgl_rejects_multiple_sges() {
+        validate_atomic_sgl(&[sge(8), sge(8)], 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "exactly 8 bytes")]
+    fn validate_atomic_sgl_rejects_wrong_length() {
+        validate_atomic_sgl(&[sge(16)], 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "8-byte-aligned")]
+    fn validate_atomic_sgl_rejects_misaligned_remote_addr() {
+        validate_atomic_sgl(&[sge(8)], 65);
+    }
+}